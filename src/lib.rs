@@ -0,0 +1,632 @@
+use regex::{self, Regex};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::num::ParseIntError;
+use std::sync::OnceLock;
+
+/// A single entry of the todo list.
+///
+/// Items are created and mutated through [`TodoList`]; consumers read them back
+/// via [`TodoList::iter`]. Each item carries a stable internal id that does not
+/// change when earlier items are removed, so prerequisite links survive edits
+/// even though the 1-based numbering shown to users shifts.
+#[derive(Debug)]
+pub struct TodoItem {
+    id: usize,
+    name: String,
+    done: bool,
+    needs: Vec<usize>,
+    priority: Option<char>,
+    tags: Vec<String>,
+    due: Option<String>,
+}
+
+impl TodoItem {
+    /// The stable internal id of the item.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// The text of the item, with the leading priority marker stripped but any
+    /// inline `@tag` / `due:` metadata left in place.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the item has been marked as done.
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// The stable ids of the items that must be done before this one.
+    pub fn needs(&self) -> &[usize] {
+        &self.needs
+    }
+
+    /// The priority letter (`A` is highest), if the item declares one.
+    pub fn priority(&self) -> Option<char> {
+        self.priority
+    }
+
+    /// The `@tag` tokens mentioned in the item text.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// The `due:YYYY-MM-DD` date, if the item declares one.
+    pub fn due(&self) -> Option<&str> {
+        self.due.as_deref()
+    }
+
+    /// Whether the item is pending and its due date has already passed,
+    /// compared against `today` (a `YYYY-MM-DD` string).
+    pub fn overdue(&self, today: &str) -> bool {
+        !self.done && self.due.as_deref().is_some_and(|d| d < today)
+    }
+}
+
+/// The single error type for the crate.
+///
+/// Every variant formats its own human-readable message through [`Display`],
+/// and [`Error::source`] chains the underlying `io`/`regex`/parse errors so
+/// library consumers can walk the cause chain.
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Regex(regex::Error),
+    /// A line that did not match the todo grammar, tagged with its 1-based
+    /// line number in the source file.
+    InvalidSyntax {
+        line: usize,
+        text: String,
+    },
+    /// An id that does not refer to any item.
+    InvalidId(usize),
+    ParseInt(ParseIntError),
+    /// An attempt to mark an item done while some of its prerequisites are not.
+    /// `blocked_by` holds the 1-based ids of the unmet prerequisites.
+    UnmetPrerequisites {
+        name: String,
+        blocked_by: Vec<usize>,
+    },
+    /// The prerequisite graph contains a cycle; holds the 1-based ids of the
+    /// items in the cycle together with any items that depend on them.
+    DependencyCycle(Vec<usize>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "error reading or writing the todo file: {}", e),
+            Error::Regex(e) => write!(f, "an unexprected regex error occured: {}", e),
+            Error::InvalidSyntax { line, text } => {
+                write!(f, "invalid syntax on line {}: \"{}\"", line, text)
+            }
+            Error::InvalidId(id) => write!(f, "invalid id {}", id),
+            Error::ParseInt(e) => write!(f, "could not parse id: {}", e),
+            Error::UnmetPrerequisites { name, blocked_by } => write!(
+                f,
+                "cannot mark \"{}\" as done: waiting on {}",
+                name,
+                join_ids(blocked_by)
+            ),
+            Error::DependencyCycle(ids) => {
+                write!(f, "dependency cycle involving items {}", join_ids(ids))
+            }
+        }
+    }
+}
+
+fn join_ids(ids: &[usize]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Regex(e) => Some(e),
+            Error::ParseInt(e) => Some(e),
+            Error::InvalidSyntax { .. }
+            | Error::InvalidId(_)
+            | Error::UnmetPrerequisites { .. }
+            | Error::DependencyCycle(_) => None,
+        }
+    }
+}
+
+impl From<regex::Error> for Error {
+    fn from(err: regex::Error) -> Self {
+        Self::Regex(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(err: ParseIntError) -> Self {
+        Self::ParseInt(err)
+    }
+}
+
+/// An in-memory todo list backed by the markdown `- [ ]` / `- [X]` file format.
+///
+/// Load one with [`TodoList::load`], mutate it through the `add`/`mark_done`/
+/// `unmark`/`remove` methods (ids are 1-based, matching the numbering shown to
+/// users) and persist it again with [`TodoList::save`]. This lets other Rust
+/// programs embed todo-file handling without shelling out to the binary.
+#[derive(Debug)]
+pub struct TodoList {
+    items: Vec<TodoItem>,
+    next_id: usize,
+}
+
+impl TodoList {
+    /// Parse the todo file at `path` into a list.
+    pub fn load(path: &str) -> Result<TodoList, Error> {
+        let file = File::options().read(true).open(path)?;
+        let items = parse_todos(&file)?;
+        let next_id = items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+        Ok(TodoList { items, next_id })
+    }
+
+    /// Append a new, not-yet-done item with no prerequisites. Any inline
+    /// priority/`@tag`/`due:` metadata in `text` is captured.
+    pub fn add(&mut self, text: &str) {
+        let (priority, name, tags, due) = split_metadata(text);
+        self.items.push(TodoItem {
+            id: self.next_id,
+            name,
+            done: false,
+            needs: Vec::new(),
+            priority,
+            tags,
+            due,
+        });
+        self.next_id += 1;
+    }
+
+    /// Order the list by priority (lettered items first, `A` before `B`) and
+    /// then by due date (earliest first); items without either sort last.
+    pub fn sort(&mut self) {
+        self.items.sort_by(|a, b| {
+            let pa = a.priority.unwrap_or('~');
+            let pb = b.priority.unwrap_or('~');
+            let da = a.due.as_deref().unwrap_or("~");
+            let db = b.due.as_deref().unwrap_or("~");
+            pa.cmp(&pb).then(da.cmp(db))
+        });
+    }
+
+    /// Mark the item with the given 1-based id as done.
+    ///
+    /// Fails with [`Error::InvalidId`] if the id is out of range and with
+    /// [`Error::UnmetPrerequisites`] if any prerequisite is not yet done.
+    pub fn mark_done(&mut self, id: usize) -> Result<(), Error> {
+        let item = self
+            .items
+            .get(id.wrapping_sub(1))
+            .filter(|_| id != 0)
+            .ok_or(Error::InvalidId(id))?;
+
+        let blocked_by: Vec<usize> = item
+            .needs
+            .iter()
+            .filter(|&&prereq| !self.is_done(prereq))
+            .filter_map(|&prereq| self.position_of(prereq))
+            .collect();
+
+        if !blocked_by.is_empty() {
+            return Err(Error::UnmetPrerequisites {
+                name: item.name.clone(),
+                blocked_by,
+            });
+        }
+
+        self.items[id - 1].done = true;
+        Ok(())
+    }
+
+    fn get_by_id(&self, id: usize) -> Option<&TodoItem> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    fn is_done(&self, id: usize) -> bool {
+        self.get_by_id(id).map(|item| item.done).unwrap_or(false)
+    }
+
+    /// The current 1-based position of the item with the given stable id.
+    fn position_of(&self, id: usize) -> Option<usize> {
+        self.items.iter().position(|item| item.id == id).map(|p| p + 1)
+    }
+
+    /// The stable ids of all items in dependency (topological) order.
+    ///
+    /// Builds a directed graph with an edge from each prerequisite to its
+    /// dependent, seeds a queue with every zero-in-degree node and repeatedly
+    /// emits a node while decrementing its dependents' in-degrees. If fewer
+    /// nodes are emitted than exist, the items still carrying a nonzero
+    /// in-degree (those in a cycle and those depending on one) are reported via
+    /// [`Error::DependencyCycle`].
+    pub fn topo_order(&self) -> Result<Vec<usize>, Error> {
+        let mut in_degree: std::collections::HashMap<usize, usize> =
+            self.items.iter().map(|item| (item.id, 0)).collect();
+        let mut dependents: std::collections::HashMap<usize, Vec<usize>> =
+            self.items.iter().map(|item| (item.id, Vec::new())).collect();
+
+        for item in &self.items {
+            for &prereq in &item.needs {
+                if let Some(list) = dependents.get_mut(&prereq) {
+                    list.push(item.id);
+                    *in_degree.get_mut(&item.id).unwrap() += 1;
+                }
+            }
+        }
+
+        let mut queue: Vec<usize> = self
+            .items
+            .iter()
+            .filter(|item| in_degree[&item.id] == 0)
+            .map(|item| item.id)
+            .collect();
+
+        let mut order = Vec::with_capacity(self.items.len());
+        while let Some(id) = queue.pop() {
+            order.push(id);
+            for &dependent in &dependents[&id] {
+                let degree = in_degree.get_mut(&dependent).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push(dependent);
+                }
+            }
+        }
+
+        if order.len() < self.items.len() {
+            let mut stuck: Vec<usize> = in_degree
+                .iter()
+                .filter(|(_, &degree)| degree > 0)
+                .filter_map(|(&id, _)| self.position_of(id))
+                .collect();
+            stuck.sort();
+            return Err(Error::DependencyCycle(stuck));
+        }
+
+        Ok(order)
+    }
+
+    /// The 1-based ids of the items that are ready to be worked on — not yet
+    /// done and with every prerequisite done — in dependency order.
+    pub fn ready(&self) -> Result<Vec<usize>, Error> {
+        let order = self.topo_order()?;
+        Ok(order
+            .into_iter()
+            .filter_map(|id| self.get_by_id(id))
+            .filter(|item| !item.done && item.needs.iter().all(|&p| self.is_done(p)))
+            .filter_map(|item| self.position_of(item.id))
+            .collect())
+    }
+
+    /// The item at the given 1-based position, if any.
+    pub fn get(&self, id: usize) -> Option<&TodoItem> {
+        self.items.get(id.wrapping_sub(1)).filter(|_| id != 0)
+    }
+
+    /// The 1-based ids of the items whose text matches `pattern`, preserving
+    /// their original numbering so they can be passed straight to
+    /// `done`/`remove`. Fails with [`Error::Regex`] if `pattern` is not a valid
+    /// regular expression.
+    pub fn find(&self, pattern: &str) -> Result<Vec<usize>, Error> {
+        let re = Regex::new(pattern)?;
+        Ok(self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| re.is_match(&item.name))
+            .map(|(idx, _)| idx + 1)
+            .collect())
+    }
+
+    /// Clear the done flag on the item with the given 1-based id. Returns
+    /// `false` if the id is out of range.
+    pub fn unmark(&mut self, id: usize) -> bool {
+        match self.items.get_mut(id.wrapping_sub(1)) {
+            Some(item) if id != 0 => {
+                item.done = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the item with the given 1-based id. Returns `false` if the id is
+    /// out of range.
+    pub fn remove(&mut self, id: usize) -> bool {
+        if id == 0 || id > self.items.len() {
+            return false;
+        }
+        self.items.remove(id - 1);
+        true
+    }
+
+    /// Number of items in the list.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterate over the items in order.
+    pub fn iter(&self) -> impl Iterator<Item = &TodoItem> {
+        self.items.iter()
+    }
+
+    /// Write the list back to `path` in the canonical markdown format.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        for todo in &self.items {
+            file.write_fmt(format_args!(
+                "- [{}] ",
+                if todo.done { "X" } else { " " }
+            ))?;
+            if let Some(priority) = todo.priority {
+                file.write_fmt(format_args!("({}) ", priority))?;
+            }
+            file.write_all(todo.name.as_bytes())?;
+            let needs: Vec<usize> = todo
+                .needs
+                .iter()
+                .filter_map(|&id| self.position_of(id))
+                .collect();
+            if !needs.is_empty() {
+                file.write_fmt(format_args!(" (needs: {})", join_ids(&needs)))?;
+            }
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_todos(file: &File) -> Result<Vec<TodoItem>, Error> {
+    let r = Regex::new(r"^- \[([ X])\] (.*?)(?: \(needs:\s*([0-9,\s]+)\))?$")?;
+
+    // The `needs:` field references the 1-based ids the items had on disk, which
+    // at load time coincide with their position; the stable id assigned below is
+    // that same position, so the references can be kept as-is after validation.
+    let items: Vec<TodoItem> = io::BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| -> Result<TodoItem, Error> {
+            let line = l?;
+            let invalid = || Error::InvalidSyntax {
+                line: idx + 1,
+                text: line.to_string(),
+            };
+            let caps = r.captures(&line).ok_or_else(invalid)?;
+
+            let done = caps.get(1).ok_or_else(invalid)?.as_str() == "X";
+            let rest = caps.get(2).ok_or_else(invalid)?.as_str();
+            let needs = match caps.get(3) {
+                Some(m) => m
+                    .as_str()
+                    .split(',')
+                    .map(|s| s.trim().parse::<usize>())
+                    .collect::<Result<Vec<usize>, ParseIntError>>()?,
+                None => Vec::new(),
+            };
+
+            let (priority, name, tags, due) = split_metadata(rest);
+
+            Ok(TodoItem {
+                id: idx + 1,
+                name,
+                done,
+                needs,
+                priority,
+                tags,
+                due,
+            })
+        })
+        .collect::<Result<Vec<TodoItem>, Error>>()?;
+
+    // Reject prerequisites that point at no item now that the count is known.
+    let count = items.len();
+    for item in &items {
+        for &prereq in &item.needs {
+            if prereq == 0 || prereq > count {
+                return Err(Error::InvalidId(prereq));
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// Split the text of an item into its leading priority marker and the rest,
+/// and scan that rest for inline `@tag` and `due:YYYY-MM-DD` metadata. The
+/// returned name keeps the tags and due date inline (so they round-trip
+/// verbatim) but has the priority prefix removed.
+fn split_metadata(text: &str) -> (Option<char>, String, Vec<String>, Option<String>) {
+    static PRIORITY_RE: OnceLock<Regex> = OnceLock::new();
+    static TAG_RE: OnceLock<Regex> = OnceLock::new();
+    static DUE_RE: OnceLock<Regex> = OnceLock::new();
+
+    let priority_re = PRIORITY_RE.get_or_init(|| Regex::new(r"^\(([A-Z])\) ").expect("valid priority regex"));
+    let tag_re = TAG_RE.get_or_init(|| Regex::new(r"@([A-Za-z0-9_-]+)").expect("valid tag regex"));
+    let due_re =
+        DUE_RE.get_or_init(|| Regex::new(r"\bdue:(\d{4}-\d{2}-\d{2})\b").expect("valid due regex"));
+
+    let (priority, name) = match priority_re.captures(text) {
+        Some(caps) => {
+            let marker = caps.get(1).unwrap().as_str().chars().next();
+            let rest = text[caps.get(0).unwrap().end()..].to_string();
+            (marker, rest)
+        }
+        None => (None, text.to_string()),
+    };
+
+    let tags = tag_re
+        .captures_iter(&name)
+        .map(|c| c[1].to_string())
+        .collect();
+    let due = due_re.captures(&name).map(|c| c[1].to_string());
+
+    (priority, name, tags, due)
+}
+
+/// Today's date as a `YYYY-MM-DD` string in UTC.
+///
+/// Derived from the system clock without pulling in a calendar crate, using
+/// Howard Hinnant's civil-from-days conversion.
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Convert a count of days since the Unix epoch into a `(year, month, day)`
+/// civil date (proleptic Gregorian calendar).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let year = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (year + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize, name: &str, done: bool, needs: Vec<usize>) -> TodoItem {
+        let (priority, name, tags, due) = split_metadata(name);
+        TodoItem {
+            id,
+            name,
+            done,
+            needs,
+            priority,
+            tags,
+            due,
+        }
+    }
+
+    fn list(items: Vec<TodoItem>) -> TodoList {
+        let next_id = items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+        TodoList { items, next_id }
+    }
+
+    fn temp_path(tag: &str) -> String {
+        format!(
+            "{}/todo_test_{}_{}.md",
+            std::env::temp_dir().display(),
+            std::process::id(),
+            tag
+        )
+    }
+
+    #[test]
+    fn topo_order_places_prerequisites_before_dependents() {
+        // item 3 depends on items 1 and 2.
+        let todos = list(vec![
+            item(1, "build", false, vec![]),
+            item(2, "test", false, vec![]),
+            item(3, "deploy", false, vec![1, 2]),
+        ]);
+
+        let order = todos.topo_order().expect("acyclic graph");
+        let pos = |id| order.iter().position(|&x| x == id).unwrap();
+        assert!(pos(1) < pos(3));
+        assert!(pos(2) < pos(3));
+        assert_eq!(order.len(), 3);
+    }
+
+    #[test]
+    fn topo_order_reports_a_cycle() {
+        // 1 needs 2 and 2 needs 1.
+        let todos = list(vec![
+            item(1, "chicken", false, vec![2]),
+            item(2, "egg", false, vec![1]),
+        ]);
+
+        match todos.topo_order() {
+            Err(Error::DependencyCycle(ids)) => assert_eq!(ids, vec![1, 2]),
+            other => panic!("expected a dependency cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn mark_done_rejects_unmet_prerequisites() {
+        let mut todos = list(vec![
+            item(1, "build", false, vec![]),
+            item(2, "deploy", false, vec![1]),
+        ]);
+
+        match todos.mark_done(2) {
+            Err(Error::UnmetPrerequisites { blocked_by, .. }) => assert_eq!(blocked_by, vec![1]),
+            other => panic!("expected unmet prerequisites, got {:?}", other),
+        }
+
+        todos.mark_done(1).expect("no prerequisites");
+        todos.mark_done(2).expect("prerequisite now done");
+    }
+
+    #[test]
+    fn metadata_round_trips_through_save() {
+        let contents =
+            "- [ ] (A) deploy @work @urgent due:2025-12-01 (needs: 2)\n- [X] build the thing\n";
+        let src = temp_path("roundtrip_in");
+        let dst = temp_path("roundtrip_out");
+        std::fs::write(&src, contents).unwrap();
+
+        let todos = TodoList::load(&src).unwrap();
+        let first = todos.iter().next().unwrap();
+        assert_eq!(first.priority(), Some('A'));
+        assert_eq!(first.tags().to_vec(), vec!["work", "urgent"]);
+        assert_eq!(first.due(), Some("2025-12-01"));
+        assert_eq!(first.needs().to_vec(), vec![2]);
+
+        todos.save(&dst).unwrap();
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), contents);
+
+        let _ = std::fs::remove_file(&src);
+        let _ = std::fs::remove_file(&dst);
+    }
+
+    #[test]
+    fn find_preserves_one_based_ids() {
+        let todos = list(vec![
+            item(1, "buy milk", false, vec![]),
+            item(2, "walk dog", false, vec![]),
+            item(3, "buy bread", false, vec![]),
+        ]);
+
+        assert_eq!(todos.find("^buy").unwrap(), vec![1, 3]);
+    }
+}
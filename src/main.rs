@@ -1,44 +1,11 @@
-use regex::{self, Regex};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
 use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, Write};
-
-#[derive(Debug)]
-struct TodoItem {
-    name: String,
-    done: bool,
-}
-
-#[derive(Debug)]
-enum ParseTodosError {
-    RegexError(regex::Error),
-    IoError(io::Error),
-    InvalidSyntax(String),
-}
-
-impl From<regex::Error> for ParseTodosError {
-    fn from(err: regex::Error) -> Self {
-        Self::RegexError(err)
-    }
-}
-
-impl From<io::Error> for ParseTodosError {
-    fn from(err: io::Error) -> Self {
-        Self::IoError(err)
-    }
-}
-
-#[derive(Debug)]
-enum ParseIdsError {
-    InvalidId(usize),
-    ParseError(std::num::ParseIntError),
-}
-
-impl From<std::num::ParseIntError> for ParseIdsError {
-    fn from(err: std::num::ParseIntError) -> Self {
-        Self::ParseError(err)
-    }
-}
+use todo::{Error, TodoItem, TodoList};
 
 #[derive(Debug)]
 enum Command {
@@ -47,9 +14,30 @@ enum Command {
     DONE,
     UNDO,
     REMOVE,
+    READY,
+    FIND,
+    SORT,
+    INTERACTIVE,
     HELP,
 }
 
+/// Which subset of the list to show.
+enum Filter {
+    All,
+    Done,
+    Pending,
+}
+
+impl Filter {
+    fn matches(&self, done: bool) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Done => done,
+            Filter::Pending => !done,
+        }
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -62,6 +50,10 @@ fn main() {
             "done" => Command::DONE,
             "undo" => Command::UNDO,
             "remove" => Command::REMOVE,
+            "ready" => Command::READY,
+            "find" => Command::FIND,
+            "sort" => Command::SORT,
+            "interactive" | "repl" => Command::INTERACTIVE,
             "help" | "-h" | "--help" => Command::HELP,
             _ => {
                 eprintln!(
@@ -76,11 +68,15 @@ fn main() {
     if let Command::HELP = command {
         println!("Usage: {} [command]", args[0]);
         println!("Commands:");
-        println!("  list: list all todo items (same as no argument)");
+        println!("  list [--done|--pending]: list all todo items (same as no argument)");
         println!("  add [items]: add items to the todo list");
         println!("  done [item ids]: mark todo items as done");
         println!("  undo [item ids]: unmark todo items as done");
         println!("  remove [item ids]: remove todo items from the list");
+        println!("  ready: list only items whose prerequisites are all done");
+        println!("  find [pattern]: list items whose text matches a regex");
+        println!("  sort: reorder the list by priority then due date");
+        println!("  interactive: drop into a prompt loop to triage the list");
         println!("  help: print this help message");
         println!("\nBy default the items are stored in $HOME/.todo.md");
         println!("This can be changed by setting the environment variable TODO_FILE");
@@ -92,33 +88,40 @@ fn main() {
         env::var("HOME").expect("HOME env variable should be set")
     ));
 
-    let file = match File::options().read(true).open(&file_name) {
-        Ok(f) => f,
+    let mut todos = match TodoList::load(&file_name) {
+        Ok(v) => v,
         Err(e) => {
-            eprintln!("Error opening file '{}': {}", file_name, e.to_string());
+            eprintln!("{}", e);
             return;
         }
     };
 
-    let mut todos = match parse_todos(&file) {
-        Ok(v) => v,
-        Err(ParseTodosError::IoError(e)) => {
-            eprintln!("Error reading file: {}", e.to_string());
+    match command {
+        Command::LIST => {
+            let filter = match parse_filter(args.get(2).map(|s| s.as_str())) {
+                Ok(f) => f,
+                Err(arg) => {
+                    eprintln!("invalid filter: {}", arg);
+                    return;
+                }
+            };
+            print_todos(&todos, &filter);
             return;
         }
-        Err(ParseTodosError::RegexError(e)) => {
-            eprintln!("An unexprected regex error occured: {}", e.to_string());
+        Command::READY => {
+            print_ready(&todos);
             return;
         }
-        Err(ParseTodosError::InvalidSyntax(line)) => {
-            eprintln!("Invalid syntax detected: \"{}\"", line);
+        Command::FIND => {
+            if args.len() < 3 {
+                eprintln!("No search pattern given");
+                return;
+            }
+            print_found(&todos, &args[2]);
             return;
         }
-    };
-
-    match command {
-        Command::LIST => {
-            print_todos(&todos);
+        Command::INTERACTIVE => {
+            run_interactive(&file_name, todos);
             return;
         }
         Command::ADD => {
@@ -126,132 +129,326 @@ fn main() {
                 eprintln!("No items to add");
                 return;
             }
-            for i in 2..args.len() {
-                todos.push(TodoItem {
-                    name: args[i].to_string(),
-                    done: false,
-                });
-            }
+            let names: Vec<&str> = args[2..].iter().map(|s| s.as_str()).collect();
+            add_todos(&mut todos, &names);
+        }
+        Command::SORT => {
+            todos.sort();
         }
         Command::DONE | Command::UNDO | Command::REMOVE => {
             if args.len() < 3 {
                 eprintln!("No item ids given");
                 return;
             }
-            let mut ids: Vec<usize> = match args
-                .get(2..args.len())
-                .unwrap()
-                .iter()
-                .map(|arg| -> Result<usize, ParseIdsError> {
-                    match arg.parse::<usize>() {
-                        Err(e) => Err(ParseIdsError::from(e)),
-                        Ok(x) => {
-                            if x > todos.len() {
-                                Err(ParseIdsError::InvalidId(x))
-                            } else {
-                                Ok(x)
-                            }
-                        }
-                    }
-                })
-                .collect::<Result<Vec<usize>, ParseIdsError>>()
-            {
+            let id_args: Vec<&str> = args[2..].iter().map(|s| s.as_str()).collect();
+            let ids = match parse_ids(&id_args, todos.len()) {
                 Ok(v) => v,
-                Err(ParseIdsError::ParseError(e)) => {
-                    eprintln!("Error parsing id: {}", e.to_string());
-                    return;
-                }
-                Err(ParseIdsError::InvalidId(id)) => {
-                    dbg!(&todos);
-                    println!("Invalid id {}", id);
+                Err(e) => {
+                    eprintln!("{}", e);
                     return;
                 }
             };
+            apply_ids(&mut todos, &command, ids);
+        }
+        Command::HELP => panic!("This should have been handled earlier"),
+    }
 
-            // sort ids descending and remove duplicates so that removing doesn't cause any issues
-            ids.sort_by(|a, b| b.cmp(a));
-            ids.dedup();
+    if let Err(e) = todos.save(&file_name) {
+        eprintln!("{}", Error::from(e));
+        return;
+    }
+    print_todos(&todos, &Filter::All);
+}
 
-            for id in ids {
-                match command {
-                    Command::DONE => {
-                        todos[id - 1].done = true;
+fn parse_filter(arg: Option<&str>) -> Result<Filter, String> {
+    match arg {
+        None => Ok(Filter::All),
+        Some("--done") => Ok(Filter::Done),
+        Some("--pending") => Ok(Filter::Pending),
+        Some(other) => Err(other.to_string()),
+    }
+}
+
+fn add_todos(todos: &mut TodoList, names: &[&str]) {
+    for name in names {
+        todos.add(name);
+    }
+}
+
+fn parse_ids(id_args: &[&str], len: usize) -> Result<Vec<usize>, Error> {
+    let mut ids: Vec<usize> = id_args
+        .iter()
+        .map(|arg| -> Result<usize, Error> {
+            let x = arg.parse::<usize>()?;
+            if x > len || x == 0 {
+                Err(Error::InvalidId(x))
+            } else {
+                Ok(x)
+            }
+        })
+        .collect::<Result<Vec<usize>, Error>>()?;
+
+    // sort ids descending and remove duplicates so that removing doesn't cause any issues
+    ids.sort_by(|a, b| b.cmp(a));
+    ids.dedup();
+    Ok(ids)
+}
+
+fn apply_ids(todos: &mut TodoList, command: &Command, ids: Vec<usize>) {
+    for id in ids {
+        match command {
+            Command::DONE => {
+                if let Err(e) = todos.mark_done(id) {
+                    eprintln!("{}", e);
+                }
+            }
+            Command::UNDO => {
+                todos.unmark(id);
+            }
+            Command::REMOVE => {
+                todos.remove(id);
+            }
+            Command::LIST
+            | Command::ADD
+            | Command::READY
+            | Command::FIND
+            | Command::SORT
+            | Command::INTERACTIVE
+            | Command::HELP => {
+                panic!("Should not be possible")
+            }
+        }
+    }
+}
+
+/// Completer for the interactive prompt: suggests the known verbs as the first
+/// word and the current item ids as arguments to `done`/`undo`/`remove`.
+struct TodoHelper {
+    ids: Vec<String>,
+}
+
+const REPL_COMMANDS: &[&str] = &[
+    "add", "done", "undo", "remove", "list", "ready", "find", "sort", "save", "help", "quit",
+    "exit",
+];
+
+impl Completer for TodoHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &line[start..pos];
+        let verb = line.split_whitespace().next().unwrap_or("");
+
+        let candidates: Vec<Pair> = if start == 0 {
+            REPL_COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .map(|c| Pair {
+                    display: c.to_string(),
+                    replacement: c.to_string(),
+                })
+                .collect()
+        } else if matches!(verb, "done" | "undo" | "remove") {
+            self.ids
+                .iter()
+                .filter(|id| id.starts_with(word))
+                .map(|id| Pair {
+                    display: id.clone(),
+                    replacement: id.clone(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for TodoHelper {
+    type Hint = String;
+}
+impl Highlighter for TodoHelper {}
+impl Validator for TodoHelper {}
+impl Helper for TodoHelper {}
+
+fn id_strings(todos: &TodoList) -> Vec<String> {
+    (1..=todos.len()).map(|i| i.to_string()).collect()
+}
+
+/// The part of an interactive input line that follows the leading verb, so that
+/// a whole multi-word task or search pattern is kept as a single argument.
+fn line_remainder(line: &str) -> &str {
+    line.trim_start()
+        .split_once(char::is_whitespace)
+        .map(|x| x.1)
+        .unwrap_or("")
+        .trim_start()
+}
+
+/// Drop the user into a prompt loop over an in-memory copy of the list. Each
+/// mutating verb persists the file and re-prints the list; `save` forces a
+/// write and `quit`/`exit` (or Ctrl-C/Ctrl-D) leaves the loop.
+fn run_interactive(file_name: &str, mut todos: TodoList) {
+    let mut rl: Editor<TodoHelper, _> = match Editor::new() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Error starting interactive mode: {}", e);
+            return;
+        }
+    };
+    rl.set_helper(Some(TodoHelper {
+        ids: id_strings(&todos),
+    }));
+
+    let history_file = env::var("TODO_HISTORY").unwrap_or(format!(
+        "{}/.todo_history",
+        env::var("HOME").expect("HOME env variable should be set")
+    ));
+    let _ = rl.load_history(&history_file);
+
+    print_todos(&todos, &Filter::All);
+
+    loop {
+        match rl.readline("todo> ") {
+            Ok(line) => {
+                let _ = rl.add_history_entry(line.as_str());
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.is_empty() {
+                    continue;
+                }
+
+                let mut mutated = false;
+                match parts[0].to_lowercase().as_str() {
+                    "list" => match parse_filter(parts.get(1).copied()) {
+                        Ok(filter) => print_todos(&todos, &filter),
+                        Err(arg) => eprintln!("invalid filter: {}", arg),
+                    },
+                    "ready" => print_ready(&todos),
+                    "find" => {
+                        if parts.len() < 2 {
+                            eprintln!("No search pattern given");
+                        } else {
+                            print_found(&todos, line_remainder(&line));
+                        }
                     }
-                    Command::UNDO => {
-                        todos[id - 1].done = false;
+                    "sort" => {
+                        todos.sort();
+                        mutated = true;
                     }
-                    Command::REMOVE => {
-                        todos.remove(id - 1);
+                    "quit" | "exit" => break,
+                    "help" => {
+                        println!(
+                            "Commands: add, done, undo, remove, list, ready, find, sort, save, help, quit"
+                        );
+                    }
+                    "save" => {
+                        if let Err(e) = todos.save(file_name) {
+                            eprintln!("{}", Error::from(e));
+                        }
+                    }
+                    "add" => {
+                        if parts.len() < 2 {
+                            eprintln!("No items to add");
+                        } else {
+                            todos.add(line_remainder(&line));
+                            mutated = true;
+                        }
+                    }
+                    verb @ ("done" | "undo" | "remove") => {
+                        if parts.len() < 2 {
+                            eprintln!("No item ids given");
+                        } else {
+                            match parse_ids(&parts[1..], todos.len()) {
+                                Ok(ids) => {
+                                    let command = match verb {
+                                        "done" => Command::DONE,
+                                        "undo" => Command::UNDO,
+                                        _ => Command::REMOVE,
+                                    };
+                                    apply_ids(&mut todos, &command, ids);
+                                    mutated = true;
+                                }
+                                Err(e) => eprintln!("{}", e),
+                            }
+                        }
+                    }
+                    other => eprintln!("invalid command: {}\nUse 'help' for help", other),
+                }
+
+                if mutated {
+                    if let Err(e) = todos.save(file_name) {
+                        eprintln!("{}", Error::from(e));
                     }
-                    Command::LIST | Command::ADD | Command::HELP => {
-                        panic!("Should not be possible")
+                    if let Some(helper) = rl.helper_mut() {
+                        helper.ids = id_strings(&todos);
                     }
+                    print_todos(&todos, &Filter::All);
                 }
             }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("Error reading line: {}", e);
+                break;
+            }
         }
-        Command::HELP => panic!("This should have been handled earlier"),
     }
 
-    if let Err(e) = wirte_todos_to_file(&file_name, &todos) {
-        eprintln!("Error writing to file: {}", e.to_string());
-        return;
-    }
-    print_todos(&todos);
+    let _ = rl.save_history(&history_file);
 }
 
-fn parse_todos(file: &File) -> Result<Vec<TodoItem>, ParseTodosError> {
-    io::BufReader::new(file)
-        .lines()
-        .map(|l| -> Result<TodoItem, ParseTodosError> {
-            match l {
-                Err(e) => Err(ParseTodosError::from(e)),
-                Ok(line) => {
-                    let r = Regex::new(r"\- \[([ X])\] (.*)")?;
-                    let caps = r
-                        .captures(&line)
-                        .ok_or(ParseTodosError::InvalidSyntax(line.to_string()))?;
-
-                    let done = caps
-                        .get(1)
-                        .ok_or(ParseTodosError::InvalidSyntax(line.to_string()))?
-                        .as_str()
-                        == "X";
-
-                    let name = caps
-                        .get(2)
-                        .ok_or(ParseTodosError::InvalidSyntax(line.to_string()))?
-                        .as_str()
-                        .to_string();
-
-                    Ok(TodoItem { name, done })
+fn print_ready(todos: &TodoList) {
+    let today = todo::today();
+    match todos.ready() {
+        Ok(ids) => {
+            for id in ids {
+                if let Some(item) = todos.get(id) {
+                    print_item(id, item, &today);
                 }
             }
-        })
-        .collect()
+        }
+        Err(e) => eprintln!("{}", e),
+    }
 }
 
-fn wirte_todos_to_file(file_name: &String, todos: &Vec<TodoItem>) -> io::Result<()> {
-    let mut file = File::options()
-        .write(true)
-        .truncate(true)
-        .open(&file_name)?;
-
-    for todo in todos {
-        file.write_fmt(format_args!(
-            "- [{}] {}\n",
-            if todo.done { "X" } else { " " },
-            todo.name
-        ))?;
+fn print_found(todos: &TodoList, pattern: &str) {
+    let today = todo::today();
+    match todos.find(pattern) {
+        Ok(ids) => {
+            for id in ids {
+                if let Some(item) = todos.get(id) {
+                    print_item(id, item, &today);
+                }
+            }
+        }
+        Err(e) => eprintln!("{}", e),
     }
-    Ok(())
 }
 
-fn print_todos(todos: &Vec<TodoItem>) {
+fn print_todos(todos: &TodoList, filter: &Filter) {
+    let today = todo::today();
     for (idx, todo) in todos.iter().enumerate() {
-        if todo.done {
-            println!("{}.\t\x1b[9m{}\x1b[m", idx + 1, todo.name);
-        } else {
-            println!("{}.\t{}", idx + 1, todo.name);
+        if filter.matches(todo.done()) {
+            print_item(idx + 1, todo, &today);
         }
     }
 }
+
+fn print_item(id: usize, item: &TodoItem, today: &str) {
+    if item.done() {
+        println!("{}.\t\x1b[9m{}\x1b[m", id, item.name());
+    } else if item.overdue(today) {
+        println!("{}.\t\x1b[31m{}\x1b[m", id, item.name());
+    } else {
+        println!("{}.\t{}", id, item.name());
+    }
+}